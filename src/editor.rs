@@ -0,0 +1,218 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use nih_plug::prelude::*;
+use nih_plug_egui::{create_egui_editor, egui, widgets, EguiState};
+
+use crate::{FilterParams, FilterType};
+
+pub(crate) fn default_state() -> Arc<EguiState> {
+    EguiState::from_size(420, 280)
+}
+
+pub(crate) fn create(
+    params: Arc<FilterParams>,
+    editor_state: Arc<EguiState>,
+    sample_rate: Arc<AtomicU32>,
+) -> Option<Box<dyn Editor>> {
+    create_egui_editor(
+        editor_state,
+        (),
+        |_, _| {},
+        move |egui_ctx, setter, _state| {
+            egui::CentralPanel::default().show(egui_ctx, |ui| {
+                ui.label("Moog Ladder Filter");
+
+                ui.horizontal(|ui| {
+                    ui.add(widgets::ParamSlider::for_param(&params.cutoff, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.resonance, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.drive, setter));
+                });
+                ui.horizontal(|ui| {
+                    ui.add(widgets::ParamSlider::for_param(&params.filter_type, setter));
+                    ui.add(widgets::ParamSlider::for_param(
+                        &params.two_pole_four_pole,
+                        setter,
+                    ));
+                    ui.add(widgets::ParamSlider::for_param(&params.hi_low_pass, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.oversample, setter));
+                });
+                ui.horizontal(|ui| {
+                    ui.add(widgets::ParamSlider::for_param(&params.attack, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.release, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.amount, setter));
+                    ui.add(widgets::ParamSlider::for_param(&params.key_track, setter));
+                });
+
+                let (response, painter) = ui.allocate_painter(
+                    egui::Vec2::new(ui.available_width(), ui.available_height()),
+                    egui::Sense::hover(),
+                );
+                draw_response_curve(
+                    &painter,
+                    response.rect,
+                    &params,
+                    f32::from_bits(sample_rate.load(Ordering::Relaxed)),
+                );
+            });
+        },
+    )
+}
+
+// Plots the magnitude response (in dB) across 20 Hz..20 kHz on a log-frequency
+// axis, computed analytically from the one-pole cascade transfer function.
+fn draw_response_curve(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    params: &FilterParams,
+    sample_rate: f32,
+) {
+    const MIN_DB: f32 = -48.0;
+    const MAX_DB: f32 = 24.0;
+    const NUM_POINTS: usize = 200;
+
+    painter.rect_filled(rect, 0.0, egui::Color32::from_gray(24));
+
+    if params.filter_type.value() != FilterType::MoogLadder {
+        // The Sallen-Key path doesn't share the ladder's closed-form
+        // transfer function (or the Huovilainen tuning fit to it), so we
+        // don't draw a curve that would misrepresent it.
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "Response curve shown for Moog Ladder only",
+            egui::FontId::default(),
+            egui::Color32::GRAY,
+        );
+        return;
+    }
+
+    let resonance = params.resonance.value();
+    let stage_count = if params.two_pole_four_pole.value() {
+        4
+    } else {
+        2
+    };
+    let is_low_pass = params.hi_low_pass.value();
+    let oversample_factor = if params.oversample.value() { 2.0 } else { 1.0 };
+    let effective_sample_rate = sample_rate * oversample_factor;
+
+    // Same clamp process() applies to the modulated cutoff, so the plot
+    // can't show an fc past the Huovilainen fit domain when the audio path
+    // wouldn't actually run there (e.g. a near-max cutoff at a low host
+    // sample rate).
+    let max_cutoff = 0.49 * effective_sample_rate;
+    let cutoff = params.cutoff.value().clamp(20.0, max_cutoff);
+
+    // Same Huovilainen v2 tuning correction the ladder uses in process(), so
+    // the plotted curve matches what's actually playing instead of diverging
+    // near Nyquist where the correction matters most.
+    let fc = cutoff / effective_sample_rate;
+    let fcr = 1.8730 * fc.powi(3) + 0.4955 * fc.powi(2) - 0.6490 * fc + 0.9988;
+    let acr = -3.9364 * fc.powi(2) + 1.8409 * fc + 0.9968;
+    let g = 1.0 - (-std::f32::consts::TAU * fc * fcr).exp();
+    let k = 4.0 * resonance * acr;
+
+    let mut points = Vec::with_capacity(NUM_POINTS);
+    for i in 0..NUM_POINTS {
+        let t = i as f32 / (NUM_POINTS - 1) as f32;
+        // 20 Hz..20 kHz, logarithmically spaced
+        let freq = 20.0 * 1_000.0f32.powf(t);
+
+        let omega = std::f32::consts::TAU * freq / effective_sample_rate;
+        let z_inv = Complex32::from_polar(1.0, -omega);
+
+        // single stage: H1(z) = g / (1 - (1-g) z^-1)
+        let h1 = Complex32::new(g, 0.0) / (Complex32::new(1.0, 0.0) - Complex32::new(1.0 - g, 0.0) * z_inv);
+        let h_cascade = h1.powi(stage_count);
+
+        // closed-loop response with the feedback resonance term
+        let h_total = h_cascade / (Complex32::new(1.0, 0.0) + Complex32::new(k, 0.0) * h_cascade);
+        let response = if is_low_pass {
+            h_total
+        } else {
+            Complex32::new(1.0, 0.0) - h_total
+        };
+
+        let mag_db = 20.0 * response.norm().max(1e-6).log10();
+
+        let x = rect.left() + t * rect.width();
+        let y_t = ((mag_db - MIN_DB) / (MAX_DB - MIN_DB)).clamp(0.0, 1.0);
+        let y = rect.bottom() - y_t * rect.height();
+        points.push(egui::pos2(x, y));
+    }
+
+    painter.add(egui::Shape::line(
+        points,
+        egui::Stroke::new(1.5, egui::Color32::from_rgb(90, 200, 250)),
+    ));
+}
+
+// Minimal complex arithmetic for the unit-circle evaluation above, kept
+// local rather than pulling in a complex-number crate for one use site.
+#[derive(Clone, Copy)]
+struct Complex32 {
+    re: f32,
+    im: f32,
+}
+
+impl Complex32 {
+    fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+
+    fn from_polar(r: f32, theta: f32) -> Self {
+        Self::new(r * theta.cos(), r * theta.sin())
+    }
+
+    fn norm(self) -> f32 {
+        (self.re * self.re + self.im * self.im).sqrt()
+    }
+
+    fn powi(self, n: i32) -> Self {
+        let mut result = Complex32::new(1.0, 0.0);
+        for _ in 0..n {
+            result = result * self;
+        }
+        result
+    }
+}
+
+impl std::ops::Add for Complex32 {
+    type Output = Complex32;
+
+    fn add(self, rhs: Self) -> Self {
+        Complex32::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl std::ops::Sub for Complex32 {
+    type Output = Complex32;
+
+    fn sub(self, rhs: Self) -> Self {
+        Complex32::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl std::ops::Mul for Complex32 {
+    type Output = Complex32;
+
+    fn mul(self, rhs: Self) -> Self {
+        Complex32::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl std::ops::Div for Complex32 {
+    type Output = Complex32;
+
+    fn div(self, rhs: Self) -> Self {
+        let denom = rhs.re * rhs.re + rhs.im * rhs.im;
+        Complex32::new(
+            (self.re * rhs.re + self.im * rhs.im) / denom,
+            (self.im * rhs.re - self.re * rhs.im) / denom,
+        )
+    }
+}
@@ -1,16 +1,86 @@
 use nih_plug::prelude::*;
+use nih_plug_egui::EguiState;
 use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
+mod editor;
+
 struct MoogLadderFilter {
     params: Arc<FilterParams>,
     prev_outputs: Vec<f32>,
     prev_w: Vec<f32>,
     g: f32,
+    // 2x oversampling state: last raw input (for the interpolated mid-sample).
+    prev_raw_input: f32,
+    // Sallen-Key state, kept separate from the ladder's prev_outputs/prev_w
+    // so switching `filter_type` never leaves the other topology's state stale.
+    sk_stage_1: f32,
+    sk_stage_2: f32,
+    // MIDI-triggered ADSR-on-cutoff envelope.
+    env_stage: EnvelopeStage,
+    env_value: f32,
+    note_velocity: f32,
+    // MIDI note (0..127) of the last note-on, for key tracking. Defaults to
+    // A4 (69) so tracking is a no-op offset until a note has been played.
+    last_note: f32,
+    // Cached coefficients, recomputed only when the effective cutoff (or
+    // sample rate) has actually moved, to skip the exp()/poly recompute
+    // on the common case of a static cutoff.
+    cached: CachedCoefficients,
+    // Last-seen host sample rate, shared with the editor (as f32 bits) so
+    // the response curve plots against the real project sample rate.
+    sample_rate: Arc<AtomicU32>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum EnvelopeStage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CachedCoefficients {
+    cutoff: f32,
+    inverse_sample_rate: f32,
+    acr: f32,
+    two_vt_g: f32,
+}
+
+impl Default for CachedCoefficients {
+    fn default() -> Self {
+        Self {
+            // Cutoff/sample rate are always positive, so these sentinels
+            // guarantee the first coefficient computation is never skipped.
+            cutoff: -1.0,
+            inverse_sample_rate: -1.0,
+            acr: 0.0,
+            two_vt_g: 0.0,
+        }
+    }
+}
+
+// How much the effective cutoff (post envelope/key-track, in Hz) must move
+// before the transcendental coefficients are considered dirty and recomputed.
+const CUTOFF_DIRTY_EPSILON_HZ: f32 = 0.05;
+
+#[derive(Enum, Debug, PartialEq, Eq)]
+enum FilterType {
+    #[id = "moog_ladder"]
+    #[name = "Moog Ladder"]
+    MoogLadder,
+    #[id = "sallen_key"]
+    #[name = "Sallen-Key"]
+    SallenKey,
 }
 
 #[derive(Params)]
 struct FilterParams {
+    #[persist = "editor-state"]
+    editor_state: Arc<EguiState>,
     #[id = "cutoff"]
     pub cutoff: FloatParam,
     #[id = "resonance"]
@@ -25,10 +95,16 @@ struct FilterParams {
     pub release: FloatParam,
     #[id = "amount"]
     pub amount: FloatParam,
+    #[id = "key_track"]
+    pub key_track: FloatParam,
     #[id = "pole"]
     pub two_pole_four_pole: BoolParam,
     #[id = "pass"]
     pub hi_low_pass: BoolParam,
+    #[id = "oversample"]
+    pub oversample: BoolParam,
+    #[id = "filter_type"]
+    pub filter_type: EnumParam<FilterType>,
 }
 
 impl Default for MoogLadderFilter {
@@ -38,6 +114,15 @@ impl Default for MoogLadderFilter {
             prev_outputs: vec![0.0, 0.0, 0.0, 0.0],
             prev_w: vec![0.0, 0.0, 0.0],
             g: 0.0,
+            prev_raw_input: 0.0,
+            sk_stage_1: 0.0,
+            sk_stage_2: 0.0,
+            env_stage: EnvelopeStage::Idle,
+            env_value: 0.0,
+            note_velocity: 1.0,
+            last_note: 69.0,
+            cached: CachedCoefficients::default(),
+            sample_rate: Arc::new(AtomicU32::new(48_000.0f32.to_bits())),
         }
     }
 }
@@ -45,6 +130,8 @@ impl Default for MoogLadderFilter {
 impl Default for FilterParams {
     fn default() -> Self {
         Self {
+            editor_state: editor::default_state(),
+
             cutoff: FloatParam::new(
                 "Cutoff",
                 20_000.0,
@@ -107,9 +194,25 @@ impl Default for FilterParams {
             .with_value_to_string(formatters::v2s_f32_percentage(2))
             .with_string_to_value(formatters::s2v_f32_percentage()),
 
+            key_track: FloatParam::new(
+                "Key Track",
+                0.0,
+                FloatRange::Linear {
+                    min: 0.0,
+                    max: 100.0,
+                },
+            )
+            .with_smoother(SmoothingStyle::Linear(10.0))
+            .with_unit(" %")
+            .with_value_to_string(formatters::v2s_f32_rounded(0)),
+
             two_pole_four_pole: BoolParam::new("2-Pole | 4-Pole", true),
 
             hi_low_pass: BoolParam::new("HP | LP", true),
+
+            oversample: BoolParam::new("Oversample 2x", false),
+
+            filter_type: EnumParam::new("Filter Type", FilterType::MoogLadder),
         }
     }
 }
@@ -138,7 +241,7 @@ impl Plugin for MoogLadderFilter {
         },
     ];
 
-    const MIDI_INPUT: MidiConfig = MidiConfig::None;
+    const MIDI_INPUT: MidiConfig = MidiConfig::Basic;
 
     const SAMPLE_ACCURATE_AUTOMATION: bool = true;
 
@@ -149,16 +252,26 @@ impl Plugin for MoogLadderFilter {
         self.params.clone()
     }
 
+    fn editor(&mut self, _async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
+        editor::create(
+            self.params.clone(),
+            self.params.editor_state.clone(),
+            self.sample_rate.clone(),
+        )
+    }
+
     fn initialize(
         &mut self,
         _audio_io_layout: &AudioIOLayout,
         buffer_config: &BufferConfig,
         _context: &mut impl InitContext<Self>,
     ) -> bool {
-        self.g = 1.0
-            - (-std::f32::consts::TAU * (self.params.cutoff.smoothed.next())
-                / buffer_config.sample_rate as f32)
-                .exp();
+        // `self.g` and the rest of the coefficient cache are derived from
+        // the Huovilainen-corrected, oversample-aware formula in process(),
+        // which always runs before any audio is produced - no need to
+        // precompute a (now stale) uncorrected approximation here.
+        self.sample_rate
+            .store(buffer_config.sample_rate.to_bits(), Ordering::Relaxed);
         true
     }
 
@@ -168,77 +281,270 @@ impl Plugin for MoogLadderFilter {
         _aux: &mut AuxiliaryBuffers,
         _context: &mut impl ProcessContext<Self>,
     ) -> ProcessStatus {
-        for channel_samples in buffer.iter_samples() {
+        let sample_rate = _context.transport().sample_rate;
+        self.sample_rate.store(sample_rate.to_bits(), Ordering::Relaxed);
+        let mut next_event = _context.next_event();
+
+        for (sample_id, channel_samples) in buffer.iter_samples().enumerate() {
+            while let Some(event) = next_event {
+                if event.timing() > sample_id as u32 {
+                    break;
+                }
+
+                match event {
+                    NoteEvent::NoteOn { note, velocity, .. } => {
+                        self.env_stage = EnvelopeStage::Attack;
+                        // Reset so a retrigger/legato note-on always gets an
+                        // audible attack ramp, even if the envelope was
+                        // still sitting in Sustain from a held note.
+                        self.env_value = 0.0;
+                        self.note_velocity = velocity;
+                        self.last_note = note as f32;
+                    }
+                    NoteEvent::NoteOff { .. } => {
+                        self.env_stage = EnvelopeStage::Release;
+                    }
+                    _ => (),
+                }
+
+                next_event = _context.next_event();
+            }
+
             // smoothed plugin params - call only once per loop
+            let filter_type = self.params.filter_type.value();
             let poles = self.params.two_pole_four_pole.value();
+            let is_low_pass = self.params.hi_low_pass.value();
+            let oversample = self.params.oversample.value();
             let cutoff = self.params.cutoff.smoothed.next();
             let drive = 1.0 + self.params.drive.smoothed.next() * 14.0;
-            let resonance = self.params.resonance.smoothed.next() * 4.0;
+            let resonance = self.params.resonance.smoothed.next();
             let output = 1.0 + self.params.output.smoothed.next() * 14.0;
-
-            let sample_rate = _context.transport().sample_rate;
-            let inverse_sample_rate = 1.0 / sample_rate;
-
-            // these values are extracted from the analog circuit
-            // but I imagine something like 0.026 is so small that it can maybe be ignored
-            self.g = 1.0 - (-std::f32::consts::TAU * (cutoff * inverse_sample_rate)).exp();
-            let two_vt = 2.0 * 0.026;
+            let attack_time = self.params.attack.smoothed.next();
+            let release_time = self.params.release.smoothed.next();
+            let amount = self.params.amount.smoothed.next();
+            let key_track = self.params.key_track.smoothed.next() / 100.0;
+
+            let env = self.next_envelope_value(attack_time, release_time, sample_rate);
+            // `amount` is in -10..10 octaves; key tracking shifts one octave
+            // per octave of note distance from A4 (MIDI 69), scaled by the
+            // 0..1 key_track. Both are applied in log-frequency space so they
+            // compose musically with each other and with the raw cutoff.
+            let envelope_octaves = env * self.note_velocity * amount;
+            let key_track_octaves = (self.last_note - 69.0) / 12.0 * key_track;
+
+            let oversample_factor = if oversample { 2.0 } else { 1.0 };
+            let inverse_sample_rate = 1.0 / (sample_rate * oversample_factor);
+
+            // The Huovilainen fcr/acr polynomials are only fit for fc in
+            // roughly 0..0.5; combined envelope + key-track modulation can
+            // otherwise push the cutoff far past Nyquist, where `g`
+            // saturates and cutoff tracking silently breaks down.
+            let max_cutoff = 0.49 * sample_rate * oversample_factor;
+            let modulated_cutoff =
+                (cutoff * 2.0f32.powf(envelope_octaves + key_track_octaves)).clamp(20.0, max_cutoff);
+
+            // thermal voltage of the transistor pairs; 1.2 keeps the stage
+            // tanh nonlinearity well-behaved, where 0.026 drives it into hard
+            // saturation
+            let two_vt = 2.0 * 1.2;
             let two_vt_reciprocal = 1.0 / two_vt;
-            let two_vt_g = two_vt * self.g as f32;
 
-            for sample in channel_samples {
-                // let input = *sample;
-                let input = (*sample * drive).tanh();
-                // true = 4 pole / false = 2 pole
-                if poles {
-                    let tanh_stage_1 = (input
-                        - ((4.0 * resonance * self.prev_outputs[3]) * two_vt_reciprocal))
-                        .tanh();
-                    let stage_1 = self.prev_outputs[0] + two_vt_g * (tanh_stage_1 - self.prev_w[0]);
-                    self.prev_outputs[0] = stage_1;
+            // The exp()/poly coefficients only depend on the effective
+            // cutoff and sample rate, which are usually unchanged from one
+            // sample to the next once smoothing has settled - skip the
+            // transcendental recompute unless they actually moved.
+            let coeffs_dirty = (modulated_cutoff - self.cached.cutoff).abs()
+                > CUTOFF_DIRTY_EPSILON_HZ
+                || self.cached.inverse_sample_rate != inverse_sample_rate;
+            if coeffs_dirty {
+                // Huovilainen's v2 tuning: a cubic/quadratic correction of
+                // the raw one-pole coefficient so cutoff tracking and
+                // resonance scaling stay accurate as fc approaches Nyquist.
+                let fc = modulated_cutoff * inverse_sample_rate;
+                let fcr = 1.8730 * fc.powi(3) + 0.4955 * fc.powi(2) - 0.6490 * fc + 0.9988;
+                let acr = -3.9364 * fc.powi(2) + 1.8409 * fc + 0.9968;
+                self.g = 1.0 - (-std::f32::consts::TAU * fc * fcr).exp();
+
+                self.cached = CachedCoefficients {
+                    cutoff: modulated_cutoff,
+                    inverse_sample_rate,
+                    acr,
+                    two_vt_g: two_vt * self.g,
+                };
+            }
+            let two_vt_g = self.cached.two_vt_g;
+            let ladder_acr = self.cached.acr;
 
-                    self.prev_w[0] = (stage_1 * two_vt_reciprocal).tanh();
+            for sample in channel_samples {
+                let raw_input = *sample;
+
+                let stage_out = if oversample {
+                    let mid_input = (self.prev_raw_input + raw_input) * 0.5;
+                    let stage_a = self.tick(&filter_type, mid_input, poles, drive, resonance, ladder_acr, two_vt_reciprocal, two_vt_g);
+                    let stage_b = self.tick(&filter_type, raw_input, poles, drive, resonance, ladder_acr, two_vt_reciprocal, two_vt_g);
+                    // half-sample delay average flattens the decimation response
+                    (stage_a + stage_b) * 0.5
+                } else {
+                    self.tick(&filter_type, raw_input, poles, drive, resonance, ladder_acr, two_vt_reciprocal, two_vt_g)
+                };
+                self.prev_raw_input = raw_input;
+
+                // true = LP / false = HP: HP is just the complement of the
+                // lowpass cascade against the (undriven) input.
+                let filtered = if is_low_pass {
+                    stage_out
+                } else {
+                    raw_input - stage_out
+                };
 
-                    let stage_2 =
-                        self.prev_outputs[1] + two_vt_g * (self.prev_w[0] - self.prev_w[1]);
-                    self.prev_outputs[1] = stage_2;
+                *sample = if filter_type == FilterType::MoogLadder && poles {
+                    (output * filtered * drive).tanh()
+                } else {
+                    (output * filtered).tanh()
+                };
+            }
+        }
 
-                    self.prev_w[1] = (stage_2 * two_vt_reciprocal).tanh();
+        ProcessStatus::Normal
+    }
 
-                    let stage_3 =
-                        self.prev_outputs[2] + two_vt_g * (self.prev_w[1] - self.prev_w[2]);
-                    self.prev_outputs[2] = stage_3;
+    fn deactivate(&mut self) {}
+}
 
-                    self.prev_w[2] = (stage_3 * two_vt_reciprocal).tanh();
+impl MoogLadderFilter {
+    // Advances the ADSR-on-cutoff envelope by one sample and returns its
+    // current value in 0..1. Attack/Release times are in seconds; there is
+    // no dedicated decay/sustain-level control, so Decay resolves to the
+    // Sustain plateau (env == 1.0) immediately.
+    fn next_envelope_value(&mut self, attack_time: f32, release_time: f32, sample_rate: f32) -> f32 {
+        let attack_inc = 1.0 / (attack_time.max(1e-4) * sample_rate);
+        let release_inc = 1.0 / (release_time.max(1e-4) * sample_rate);
+
+        match self.env_stage {
+            EnvelopeStage::Idle => self.env_value = 0.0,
+            EnvelopeStage::Attack => {
+                self.env_value += attack_inc;
+                if self.env_value >= 1.0 {
+                    self.env_value = 1.0;
+                    self.env_stage = EnvelopeStage::Decay;
+                }
+            }
+            EnvelopeStage::Decay => self.env_stage = EnvelopeStage::Sustain,
+            EnvelopeStage::Sustain => self.env_value = 1.0,
+            EnvelopeStage::Release => {
+                self.env_value -= release_inc;
+                if self.env_value <= 0.0 {
+                    self.env_value = 0.0;
+                    self.env_stage = EnvelopeStage::Idle;
+                }
+            }
+        }
 
-                    let stage_4 = self.prev_outputs[3]
-                        + two_vt_g
-                            * (self.prev_w[2] - (self.prev_outputs[3] * two_vt_reciprocal).tanh());
+        self.env_value
+    }
 
-                    *sample = (output * stage_4 * drive).tanh();
-                    self.prev_outputs[3] = stage_4;
-                } else {
-                    let tanh_stage_1 = (input
-                        - ((4.0 * resonance * self.prev_outputs[1]) * two_vt_reciprocal))
-                        .tanh();
-                    let stage_1 = self.prev_outputs[0] + two_vt_g * (tanh_stage_1 - self.prev_w[0]);
-                    self.prev_outputs[0] = stage_1;
-                    self.prev_w[0] = (stage_1 * two_vt_reciprocal).tanh();
-
-                    let stage_2 = self.prev_outputs[1]
-                        + two_vt_g
-                            * (self.prev_w[0] - (self.prev_outputs[1] * two_vt_reciprocal).tanh());
-
-                    *sample = (output * stage_2).tanh();
-                    self.prev_outputs[1] = stage_2;
-                }
+    // Dispatches one pass (one oversampled sample) to the selected topology
+    // and returns the raw stage output, before output/drive shaping. Each
+    // topology derives its own feedback scaling from the raw `resonance` -
+    // `ladder_acr` (Huovilainen's correction) only applies to the ladder's
+    // feedback loop, not the structurally different Sallen-Key one.
+    fn tick(
+        &mut self,
+        filter_type: &FilterType,
+        raw_input: f32,
+        poles: bool,
+        drive: f32,
+        resonance: f32,
+        ladder_acr: f32,
+        two_vt_reciprocal: f32,
+        two_vt_g: f32,
+    ) -> f32 {
+        match filter_type {
+            FilterType::MoogLadder => {
+                let resonance_fb = 4.0 * resonance * ladder_acr;
+                self.tick_ladder(raw_input, poles, drive, resonance_fb, two_vt_reciprocal, two_vt_g)
+            }
+            FilterType::SallenKey => {
+                let resonance_fb = 4.0 * resonance;
+                self.tick_sallen_key(raw_input, drive, resonance_fb, two_vt_reciprocal, two_vt_g)
             }
         }
+    }
 
-        ProcessStatus::Normal
+    fn tick_ladder(
+        &mut self,
+        raw_input: f32,
+        poles: bool,
+        drive: f32,
+        resonance_fb: f32,
+        two_vt_reciprocal: f32,
+        two_vt_g: f32,
+    ) -> f32 {
+        let input = (raw_input * drive).tanh();
+        // true = 4 pole / false = 2 pole
+        if poles {
+            let tanh_stage_1 =
+                (input - (resonance_fb * self.prev_outputs[3]) * two_vt_reciprocal).tanh();
+            let stage_1 = self.prev_outputs[0] + two_vt_g * (tanh_stage_1 - self.prev_w[0]);
+            self.prev_outputs[0] = stage_1;
+
+            self.prev_w[0] = (stage_1 * two_vt_reciprocal).tanh();
+
+            let stage_2 = self.prev_outputs[1] + two_vt_g * (self.prev_w[0] - self.prev_w[1]);
+            self.prev_outputs[1] = stage_2;
+
+            self.prev_w[1] = (stage_2 * two_vt_reciprocal).tanh();
+
+            let stage_3 = self.prev_outputs[2] + two_vt_g * (self.prev_w[1] - self.prev_w[2]);
+            self.prev_outputs[2] = stage_3;
+
+            self.prev_w[2] = (stage_3 * two_vt_reciprocal).tanh();
+
+            let stage_4 = self.prev_outputs[3]
+                + two_vt_g * (self.prev_w[2] - (self.prev_outputs[3] * two_vt_reciprocal).tanh());
+            self.prev_outputs[3] = stage_4;
+
+            stage_4
+        } else {
+            let tanh_stage_1 =
+                (input - (resonance_fb * self.prev_outputs[1]) * two_vt_reciprocal).tanh();
+            let stage_1 = self.prev_outputs[0] + two_vt_g * (tanh_stage_1 - self.prev_w[0]);
+            self.prev_outputs[0] = stage_1;
+            self.prev_w[0] = (stage_1 * two_vt_reciprocal).tanh();
+
+            let stage_2 = self.prev_outputs[1]
+                + two_vt_g * (self.prev_w[0] - (self.prev_outputs[1] * two_vt_reciprocal).tanh());
+            self.prev_outputs[1] = stage_2;
+
+            stage_2
+        }
     }
 
-    fn deactivate(&mut self) {}
+    // Two cascaded one-pole stages with tanh feedback around the second
+    // stage, giving a snappier, differently-behaved self-oscillation than
+    // the ladder's four-stage feedback loop.
+    fn tick_sallen_key(
+        &mut self,
+        raw_input: f32,
+        drive: f32,
+        resonance_fb: f32,
+        two_vt_reciprocal: f32,
+        two_vt_g: f32,
+    ) -> f32 {
+        let input = (raw_input * drive).tanh();
+
+        let feedback = (resonance_fb * self.sk_stage_2 * two_vt_reciprocal).tanh();
+        let tanh_stage_1 = (input - feedback).tanh();
+        let stage_1 =
+            self.sk_stage_1 + two_vt_g * (tanh_stage_1 - (self.sk_stage_1 * two_vt_reciprocal).tanh());
+        self.sk_stage_1 = stage_1;
+
+        let stage_2 = self.sk_stage_2
+            + two_vt_g * ((stage_1 * two_vt_reciprocal).tanh() - (self.sk_stage_2 * two_vt_reciprocal).tanh());
+        self.sk_stage_2 = stage_2;
+
+        stage_2
+    }
 }
 
 impl ClapPlugin for MoogLadderFilter {